@@ -0,0 +1,173 @@
+//! A compact binary cache format for [`WDLData`], so repeated runs over
+//! the same months of a Lichess dump don't have to reparse the raw PGN
+//! every time. The encoding is a small byte-packed buffer in the style of
+//! the fixed-layout binary formats used for replay/export data: a magic
+//! header followed by one length-prefixed `(rating_diff, value, total)`
+//! record run per dataset.
+
+use std::fs::File;
+use std::io::{Read, Write};
+
+use super::{AnalysisConfig, Datapoint, Dataset, WDLData};
+
+const MAGIC: &[u8; 4] = b"WDL1";
+
+fn write_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.push((value & 0xff) as u8);
+    buf.push(((value >> 8) & 0xff) as u8);
+    buf.push(((value >> 16) & 0xff) as u8);
+    buf.push(((value >> 24) & 0xff) as u8);
+}
+
+fn write_i32(buf: &mut Vec<u8>, value: i32) {
+    write_u32(buf, value as u32);
+}
+
+fn read_u32(bytes: &[u8], pos: &mut usize) -> u32 {
+    let value = (bytes[*pos] as u32)
+        | ((bytes[*pos + 1] as u32) << 8)
+        | ((bytes[*pos + 2] as u32) << 16)
+        | ((bytes[*pos + 3] as u32) << 24);
+    *pos += 4;
+    value
+}
+
+fn read_i32(bytes: &[u8], pos: &mut usize) -> i32 {
+    read_u32(bytes, pos) as i32
+}
+
+fn write_dataset(buf: &mut Vec<u8>, dataset: &Dataset) {
+    write_u32(buf, dataset.len() as u32);
+    for (rating_diff, point) in dataset {
+        write_i32(buf, *rating_diff);
+        write_u32(buf, point.value);
+        write_u32(buf, point.total);
+    }
+}
+
+fn read_dataset(bytes: &[u8], pos: &mut usize) -> Dataset {
+    let count = read_u32(bytes, pos);
+    let mut dataset = Dataset::new();
+
+    for _ in 0..count {
+        let rating_diff = read_i32(bytes, pos);
+        let value = read_u32(bytes, pos);
+        let total = read_u32(bytes, pos);
+        dataset.insert(rating_diff, Datapoint { value, total });
+    }
+
+    dataset
+}
+
+/// Writes `data` to `path` in the `.wdl` binary cache format. The rating
+/// bucket size `data` was computed with is stored in the header so a
+/// later `load` can refuse to silently mix incompatible bucketings.
+pub fn save(data: &WDLData, config: &AnalysisConfig, path: &str) {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(MAGIC);
+    write_i32(&mut buf, config.rating_bucket_size);
+    write_dataset(&mut buf, &data.wins);
+    write_dataset(&mut buf, &data.draws);
+    write_dataset(&mut buf, &data.losses);
+
+    File::create(path)
+        .expect("Failed to create the cache file.")
+        .write_all(&buf)
+        .expect("Failed to write the cache file.");
+}
+
+/// Reads a `.wdl` binary cache file written by [`save`] back into a
+/// [`WDLData`]. Panics if the cache was computed with a different rating
+/// bucket size than `config`, since its keys wouldn't line up with data
+/// freshly computed under `config`.
+pub fn load(path: &str, config: &AnalysisConfig) -> WDLData {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .expect("One of the given cache files doesn't exist.")
+        .read_to_end(&mut bytes)
+        .expect("Failed to read one of the given cache files.");
+
+    assert_eq!(
+        &bytes[0..4],
+        MAGIC,
+        "Cache file doesn't start with the expected WDL1 magic header."
+    );
+
+    let mut pos = 4;
+    let cached_bucket_size = read_i32(&bytes, &mut pos);
+    assert_eq!(
+        cached_bucket_size, config.rating_bucket_size,
+        "Cache file {} was computed with rating_bucket_size {}, but the current \
+         AnalysisConfig uses {}; its buckets wouldn't line up with freshly computed data.",
+        path, cached_bucket_size, config.rating_bucket_size
+    );
+
+    let wins = read_dataset(&bytes, &mut pos);
+    let draws = read_dataset(&bytes, &mut pos);
+    let losses = read_dataset(&bytes, &mut pos);
+
+    WDLData {
+        wins,
+        draws,
+        losses,
+    }
+}
+
+/// True when `path` looks like a precomputed `.wdl` cache rather than a
+/// raw PGN export, so callers can mix both kinds of input file.
+pub fn is_cache_file(path: &str) -> bool {
+    path.ends_with(".wdl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let mut wins = Dataset::new();
+        wins.insert(25, Datapoint { value: 3, total: 7 });
+        wins.insert(-50, Datapoint { value: 0, total: 2 });
+        let mut draws = Dataset::new();
+        draws.insert(25, Datapoint { value: 1, total: 7 });
+        let mut losses = Dataset::new();
+        losses.insert(25, Datapoint { value: 3, total: 7 });
+
+        let data = WDLData {
+            wins,
+            draws,
+            losses,
+        };
+
+        let config = AnalysisConfig::new();
+        let path = "test_cache_roundtrip.wdl";
+        save(&data, &config, path);
+        let loaded = load(path, &config);
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded.wins.get(&25).unwrap().total, 7);
+        assert_eq!(loaded.wins.get(&-50).unwrap().value, 0);
+        assert_eq!(loaded.draws.get(&25).unwrap().value, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "wouldn't line up")]
+    fn test_load_rejects_mismatched_bucket_size() {
+        let data = WDLData {
+            wins: Dataset::new(),
+            draws: Dataset::new(),
+            losses: Dataset::new(),
+        };
+
+        let path = "test_cache_bucket_mismatch.wdl";
+        save(&data, &AnalysisConfig::new().rating_bucket_size(25), path);
+        load(path, &AnalysisConfig::new().rating_bucket_size(50));
+    }
+
+    #[test]
+    fn test_is_cache_file() {
+        assert!(is_cache_file("2018-01.wdl"));
+        assert!(!is_cache_file("2018-01.pgn"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Tunable knobs for a single analysis run, built with the same chained
+//! setter pattern used elsewhere for configuring games: start from
+//! [`AnalysisConfig::new`] and override only what you need.
+
+/// Controls which games qualify for analysis and how the results are
+/// bucketed and presented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalysisConfig {
+    pub(crate) min_time_control_estimate: i32,
+    pub(crate) estimated_move_count: i32,
+    pub(crate) rating_bucket_size: i32,
+    pub(crate) min_bucket_samples: u32,
+}
+
+impl AnalysisConfig {
+    /// The defaults this crate has always used: a 480-second estimate
+    /// over 40 moves, 25-point rating buckets, and no minimum sample size.
+    pub fn new() -> AnalysisConfig {
+        AnalysisConfig {
+            min_time_control_estimate: 480,
+            estimated_move_count: 40,
+            rating_bucket_size: 25,
+            min_bucket_samples: 0,
+        }
+    }
+
+    /// The minimum `initial + estimated_move_count * increment` a game's
+    /// time control must reach to qualify (e.g. 480 to keep only games
+    /// slower than blitz).
+    pub fn min_time_control_estimate(mut self, value: i32) -> AnalysisConfig {
+        self.min_time_control_estimate = value;
+        self
+    }
+
+    /// The move count assumed when estimating a game's length from its
+    /// time control.
+    pub fn estimated_move_count(mut self, value: i32) -> AnalysisConfig {
+        self.estimated_move_count = value;
+        self
+    }
+
+    /// The rating-difference granularity buckets are rounded to. Must be
+    /// positive, since it's used as a division step when rounding ratings.
+    pub fn rating_bucket_size(mut self, value: i32) -> AnalysisConfig {
+        assert!(value > 0, "rating_bucket_size must be positive, got {}", value);
+        self.rating_bucket_size = value;
+        self
+    }
+
+    /// Buckets with fewer than this many total games are omitted from
+    /// presentation.
+    pub fn min_bucket_samples(mut self, value: u32) -> AnalysisConfig {
+        self.min_bucket_samples = value;
+        self
+    }
+
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> AnalysisConfig {
+        AnalysisConfig::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let config = AnalysisConfig::new();
+        assert_eq!(config.min_time_control_estimate, 480);
+        assert_eq!(config.estimated_move_count, 40);
+        assert_eq!(config.rating_bucket_size, 25);
+        assert_eq!(config.min_bucket_samples, 0);
+    }
+
+    #[test]
+    fn test_chained_overrides() {
+        let config = AnalysisConfig::new()
+            .min_time_control_estimate(600)
+            .estimated_move_count(60)
+            .rating_bucket_size(50)
+            .min_bucket_samples(100);
+
+        assert_eq!(config.min_time_control_estimate, 600);
+        assert_eq!(config.estimated_move_count, 60);
+        assert_eq!(config.rating_bucket_size, 50);
+        assert_eq!(config.min_bucket_samples, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "rating_bucket_size must be positive")]
+    fn test_rejects_non_positive_bucket_size() {
+        AnalysisConfig::new().rating_bucket_size(0);
+    }
+}
@@ -1,8 +1,21 @@
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
+use std::io::Read;
 use std::fs::File;
 use std::cmp;
 
+extern crate nom;
+
+pub mod cache;
+mod config;
+mod model;
+mod output;
+mod pgn;
+
+pub use config::AnalysisConfig;
+pub use output::OutputFormat;
+
+use pgn::{parse_games, Game};
+
 trait Aggregatable {
     fn aggregate(self, other: Self) -> Self;
 }
@@ -87,8 +100,13 @@ impl std::ops::Not for GameResult {
     }
 }
 
-pub fn calculate_from_files<'a, I>(files: I)
-where
+pub fn calculate_from_files<'a, I>(
+    files: I,
+    config: &AnalysisConfig,
+    format: OutputFormat,
+    destination: Option<&str>,
+    cache_destination: Option<&str>,
+) where
     I: IntoIterator<Item = &'a str>,
 {
     let mut total_data = WDLData {
@@ -97,89 +115,62 @@ where
         losses: Dataset::new(),
     };
     for path in files {
-        total_data = calculate(path).aggregate(total_data);
+        let file_data = if cache::is_cache_file(path) {
+            cache::load(path, config)
+        } else {
+            calculate(path, config)
+        };
+        total_data = file_data.aggregate(total_data);
+    }
+
+    if let Some(cache_path) = cache_destination {
+        cache::save(&total_data, config, cache_path);
     }
-    wdldata_presentation(&total_data);
+
+    output::present(&total_data, config, format, destination);
 }
 
-fn calculate(file_path: &str) -> WDLData {
+fn calculate(file_path: &str, config: &AnalysisConfig) -> WDLData {
     let mut processed: u32 = 0;
 
     let mut wins = Dataset::new();
     let mut draws = Dataset::new();
     let mut losses = Dataset::new();
 
-    let file =
-        BufReader::new(File::open(file_path).expect("One of the given files doesn't exist."));
-
-    let mut skip = false;
-    let mut rating1 = 0;
-    let mut rating2 = 0;
-    let mut result_white_pov = GameResult::Unknown;
-
-    for l in file.lines() {
-        let line = l.unwrap();
+    let mut contents = String::new();
+    File::open(file_path)
+        .expect("One of the given files doesn't exist.")
+        .read_to_string(&mut contents)
+        .expect("Failed to read one of the given files.");
 
-        if line.starts_with("[TimeControl") {
-            let tc = line.split("\"").nth(1).unwrap();
-            let qualified_tc = timecontrol_qualifies(tc);
-            skip = !qualified_tc;
-
-            processed += 1;
-            if processed % 100000 == 0 {
-                println!("{} processed in the current file", processed);
-            }
-        } else if line.starts_with("[WhiteElo") {
-            rating1 = line.split("\"").nth(1).unwrap().parse().unwrap();
-        } else if line.starts_with("[BlackElo") {
-            rating2 = line.split("\"").nth(1).unwrap().parse().unwrap();
-        } else if line.starts_with("[Result") {
-            let result_str = line.split("\"").nth(1).unwrap();
-            match result_str {
-                "1-0" => result_white_pov = GameResult::Win,
-                "1/2-1/2" => result_white_pov = GameResult::Draw,
-                "0-1" => result_white_pov = GameResult::Loss,
-                "*" => result_white_pov = GameResult::Unfinished,
-                _ => panic!("Unexpected result value {:?}", result_str),
-            }
+    for game in parse_games(&contents) {
+        processed += 1;
+        if processed % 100000 == 0 {
+            println!("{} processed in the current file", processed);
         }
 
-        if !line.starts_with("[") && !skip && result_white_pov != GameResult::Unfinished
-            && rating1 != rating2
-        {
-            let min_rating = cmp::min(rating1, rating2);
-            let max_rating = cmp::max(rating1, rating2);
-            let rating_diff = round_rating(max_rating) - round_rating(min_rating);
-
-            let result_lowest_pov = if min_rating == rating1 {
-                // White is the lowest rated player.
-                result_white_pov
-            } else {
-                // Black is the lowest rated player.
-                !result_white_pov
-            };
-
-            if !wins.contains_key(&rating_diff) {
-                wins.insert(rating_diff, Datapoint { value: 0, total: 0 });
-            }
-            wins.get_mut(&rating_diff).unwrap().total += 1;
-            if !draws.contains_key(&rating_diff) {
-                draws.insert(rating_diff, Datapoint { value: 0, total: 0 });
-            }
-            draws.get_mut(&rating_diff).unwrap().total += 1;
-            if !losses.contains_key(&rating_diff) {
-                losses.insert(rating_diff, Datapoint { value: 0, total: 0 });
-            }
-            losses.get_mut(&rating_diff).unwrap().total += 1;
-
-            let mut relevant_set = match result_lowest_pov {
+        if let Some(datapoint) = wdl_datapoint(&game, config) {
+            let rating_diff = datapoint.rating_diff;
+
+            wins.entry(rating_diff)
+                .or_insert_with(|| Datapoint { value: 0, total: 0 })
+                .total += 1;
+            draws
+                .entry(rating_diff)
+                .or_insert_with(|| Datapoint { value: 0, total: 0 })
+                .total += 1;
+            losses
+                .entry(rating_diff)
+                .or_insert_with(|| Datapoint { value: 0, total: 0 })
+                .total += 1;
+
+            let relevant_set = match datapoint.result_lowest_pov {
                 GameResult::Win => &mut wins,
                 GameResult::Draw => &mut draws,
                 GameResult::Loss => &mut losses,
                 _ => panic!("result = Unknown"),
             };
             relevant_set.get_mut(&rating_diff).unwrap().value += 1;
-            skip = true;
         }
     }
 
@@ -190,7 +181,57 @@ fn calculate(file_path: &str) -> WDLData {
     }
 }
 
-fn timecontrol_qualifies(timecontrol: &str) -> bool {
+struct GameDatapoint {
+    rating_diff: i32,
+    result_lowest_pov: GameResult,
+}
+
+/// Turns a parsed [`Game`] into the datapoint `calculate` aggregates, or
+/// `None` if the game doesn't qualify (unfinished, equal ratings, or a
+/// time control that doesn't meet the threshold).
+fn wdl_datapoint(game: &Game, config: &AnalysisConfig) -> Option<GameDatapoint> {
+    let tc = game.header("TimeControl")?;
+    if !timecontrol_qualifies(tc, config) {
+        return None;
+    }
+
+    let rating1: i32 = game.header("WhiteElo")?.parse().ok()?;
+    let rating2: i32 = game.header("BlackElo")?.parse().ok()?;
+    if rating1 == rating2 {
+        return None;
+    }
+
+    let result_white_pov = match game.header("Result")? {
+        "1-0" => GameResult::Win,
+        "1/2-1/2" => GameResult::Draw,
+        "0-1" => GameResult::Loss,
+        "*" => GameResult::Unfinished,
+        other => panic!("Unexpected result value {:?}", other),
+    };
+    if result_white_pov == GameResult::Unfinished {
+        return None;
+    }
+
+    let min_rating = cmp::min(rating1, rating2);
+    let max_rating = cmp::max(rating1, rating2);
+    let rating_diff =
+        round_rating(max_rating, config.rating_bucket_size) - round_rating(min_rating, config.rating_bucket_size);
+
+    let result_lowest_pov = if min_rating == rating1 {
+        // White is the lowest rated player.
+        result_white_pov
+    } else {
+        // Black is the lowest rated player.
+        !result_white_pov
+    };
+
+    Some(GameDatapoint {
+        rating_diff,
+        result_lowest_pov,
+    })
+}
+
+fn timecontrol_qualifies(timecontrol: &str, config: &AnalysisConfig) -> bool {
     if !timecontrol.contains("+") {
         return false;
     }
@@ -199,80 +240,15 @@ fn timecontrol_qualifies(timecontrol: &str) -> bool {
 
     let initial: i32 = parts.next().unwrap().parse().unwrap();
     let increment: i32 = parts.next().unwrap().parse().unwrap();
-    let estimate = initial + 40 * increment;
+    let estimate = initial + config.estimated_move_count * increment;
 
-    estimate >= 480
+    estimate >= config.min_time_control_estimate
 }
 
-fn round_rating(rating: i32) -> i32 {
+fn round_rating(rating: i32, bucket_size: i32) -> i32 {
     let rating = rating as f32;
-    ((rating * 4.0 / 100.0).round() * 100.0 / 4.0) as i32
-}
-
-fn wdldata_presentation(data: &WDLData) {
-    println!("Wins");
-    println!("----------");
-    let mut win_strings: Vec<(i32, String)> = vec![];
-    for key in data.wins.keys() {
-        let point = data.wins.get(key).unwrap();
-        win_strings.push((
-            *key,
-            format!(
-                "+{}: {} ({}/{})",
-                *key,
-                point.percentage_value(),
-                point.value,
-                point.total
-            ),
-        ));
-    }
-    win_strings.sort_by(|a, b| a.0.cmp(&b.0));
-    for s in win_strings {
-        println!("{}", s.1);
-    }
-    println!("----------");
-    println!("Draws");
-    println!("----------");
-    let mut draw_strings: Vec<(i32, String)> = vec![];
-    for key in data.draws.keys() {
-        let point = data.draws.get(key).unwrap();
-        draw_strings.push((
-            *key,
-            format!(
-                "+{}: {} ({}/{})",
-                *key,
-                point.percentage_value(),
-                point.value,
-                point.total
-            ),
-        ));
-    }
-    draw_strings.sort_by(|a, b| a.0.cmp(&b.0));
-    for s in draw_strings {
-        println!("{}", s.1);
-    }
-    println!("----------");
-    println!("Losses");
-    println!("----------");
-    let mut loss_strings: Vec<(i32, String)> = vec![];
-    for key in data.losses.keys() {
-        let point = data.losses.get(key).unwrap();
-        loss_strings.push((
-            *key,
-            format!(
-                "+{}: {} ({}/{})",
-                *key,
-                point.percentage_value(),
-                point.value,
-                point.total
-            ),
-        ));
-    }
-    loss_strings.sort_by(|a, b| a.0.cmp(&b.0));
-    for s in loss_strings {
-        println!("{}", s.1);
-    }
-    println!("----------");
+    let bucket_size = bucket_size as f32;
+    ((rating / bucket_size).round() * bucket_size) as i32
 }
 
 #[cfg(test)]
@@ -280,9 +256,9 @@ mod tests {
     use super::round_rating;
     #[test]
     pub fn test_round_rating() {
-        assert_eq!(round_rating(1712), 1700);
-        assert_eq!(round_rating(1713), 1725);
-        assert_eq!(round_rating(848), 850);
-        assert_eq!(round_rating(2093), 2100);
+        assert_eq!(round_rating(1712, 25), 1700);
+        assert_eq!(round_rating(1713, 25), 1725);
+        assert_eq!(round_rating(848, 25), 850);
+        assert_eq!(round_rating(2093, 25), 2100);
     }
 }
@@ -3,17 +3,97 @@ use std::io;
 extern crate time;
 extern crate winning_chances_core;
 
-use winning_chances_core::calculate_from_files;
+use winning_chances_core::{calculate_from_files, AnalysisConfig, OutputFormat};
 
 fn main() {
     let start = time::PreciseTime::now();
-    println!("Enter the file paths, comma-separated:");
+    println!("Enter the file paths, comma-separated (.pgn or cached .wdl files):");
     let mut files = String::new();
     io::stdin()
         .read_line(&mut files)
         .expect("Failed to read file paths.");
     let files = files.trim().split(",");
-    calculate_from_files(files);
+
+    println!("Output format (text/csv/json/markdown, default text):");
+    let mut format = String::new();
+    io::stdin()
+        .read_line(&mut format)
+        .expect("Failed to read the output format.");
+    let format = parse_format(format.trim());
+
+    println!("Output file path (leave empty to print to the console):");
+    let mut destination = String::new();
+    io::stdin()
+        .read_line(&mut destination)
+        .expect("Failed to read the output file path.");
+    let destination = destination.trim();
+    let destination = if destination.is_empty() {
+        None
+    } else {
+        Some(destination)
+    };
+
+    println!("Cache file to write the combined results to (leave empty to skip):");
+    let mut cache_destination = String::new();
+    io::stdin()
+        .read_line(&mut cache_destination)
+        .expect("Failed to read the cache file path.");
+    let cache_destination = cache_destination.trim();
+    let cache_destination = if cache_destination.is_empty() {
+        None
+    } else {
+        Some(cache_destination)
+    };
+
+    println!("Minimum time control estimate in seconds to qualify a game (leave empty for 480):");
+    let mut min_time_control_estimate = String::new();
+    io::stdin()
+        .read_line(&mut min_time_control_estimate)
+        .expect("Failed to read the minimum time control estimate.");
+    let min_time_control_estimate: i32 = min_time_control_estimate.trim().parse().unwrap_or(480);
+
+    println!("Move count assumed when estimating a game's length (leave empty for 40):");
+    let mut estimated_move_count = String::new();
+    io::stdin()
+        .read_line(&mut estimated_move_count)
+        .expect("Failed to read the estimated move count.");
+    let estimated_move_count: i32 = estimated_move_count.trim().parse().unwrap_or(40);
+
+    println!("Rating bucket size (leave empty for 25):");
+    let mut rating_bucket_size = String::new();
+    io::stdin()
+        .read_line(&mut rating_bucket_size)
+        .expect("Failed to read the rating bucket size.");
+    let rating_bucket_size: i32 = rating_bucket_size
+        .trim()
+        .parse()
+        .ok()
+        .filter(|value| *value > 0)
+        .unwrap_or(25);
+
+    println!("Minimum samples per rating bucket to report (leave empty for no minimum):");
+    let mut min_bucket_samples = String::new();
+    io::stdin()
+        .read_line(&mut min_bucket_samples)
+        .expect("Failed to read the minimum bucket sample size.");
+    let min_bucket_samples: u32 = min_bucket_samples.trim().parse().unwrap_or(0);
+
+    let config = AnalysisConfig::new()
+        .min_time_control_estimate(min_time_control_estimate)
+        .estimated_move_count(estimated_move_count)
+        .rating_bucket_size(rating_bucket_size)
+        .min_bucket_samples(min_bucket_samples);
+
+    calculate_from_files(files, &config, format, destination, cache_destination);
     let duration = start.to(time::PreciseTime::now());
     println!("Time: {} seconds", duration.num_seconds());
 }
+
+fn parse_format(format: &str) -> OutputFormat {
+    match format.to_lowercase().as_str() {
+        "csv" => OutputFormat::Csv,
+        "json" => OutputFormat::Json,
+        "markdown" | "md" => OutputFormat::Markdown,
+        _ => OutputFormat::Text,
+    }
+}
@@ -0,0 +1,180 @@
+//! Fits the bucketed win/draw/loss ratios to a closed-form winning-chances
+//! model using simulated annealing, so `output::present` can report a
+//! formula instead of just the raw empirical table.
+
+extern crate rand;
+
+use rand::Rng;
+
+use super::{Dataset, WDLData};
+
+/// Parameters of the Davidson draw model: `S` is the Elo scale (how many
+/// rating points correspond to a factor-of-ten change in odds) and `nu`
+/// is the draw propensity.
+#[derive(Debug, Clone, Copy)]
+pub struct DavidsonParams {
+    pub scale: f64,
+    pub draw_param: f64,
+}
+
+/// The fitted parameters together with the weighted squared residual they
+/// achieved against the empirical buckets.
+#[derive(Debug, Clone, Copy)]
+pub struct DavidsonFit {
+    pub params: DavidsonParams,
+    pub error: f64,
+}
+
+struct Bucket {
+    rating_diff: f64,
+    total: f64,
+    win_ratio: f64,
+    draw_ratio: f64,
+    loss_ratio: f64,
+}
+
+/// `(P(high wins), P(draw), P(low wins))` predicted by the model for a
+/// given positive rating difference `d`.
+fn predict(d: f64, params: &DavidsonParams) -> (f64, f64, f64) {
+    let x = 10f64.powf(d / params.scale);
+    let denom = x + 1.0 + params.draw_param * x.sqrt();
+
+    (x / denom, (params.draw_param * x.sqrt()) / denom, 1.0 / denom)
+}
+
+fn weighted_squared_error(buckets: &[Bucket], params: &DavidsonParams) -> f64 {
+    buckets
+        .iter()
+        .map(|bucket| {
+            let (p_high, p_draw, p_low) = predict(bucket.rating_diff, params);
+            let residual = (p_high - bucket.loss_ratio).powi(2)
+                + (p_draw - bucket.draw_ratio).powi(2)
+                + (p_low - bucket.win_ratio).powi(2);
+            bucket.total * residual
+        })
+        .sum()
+}
+
+fn buckets_from(wins: &Dataset, draws: &Dataset, losses: &Dataset) -> Vec<Bucket> {
+    let mut buckets: Vec<Bucket> = vec![];
+
+    for rating_diff in wins.keys() {
+        let win = wins.get(rating_diff).unwrap();
+        let draw = draws.get(rating_diff).unwrap();
+        let loss = losses.get(rating_diff).unwrap();
+        let total = win.total as f64;
+
+        if total == 0.0 {
+            continue;
+        }
+
+        buckets.push(Bucket {
+            rating_diff: *rating_diff as f64,
+            total,
+            win_ratio: win.value as f64 / total,
+            draw_ratio: draw.value as f64 / total,
+            loss_ratio: loss.value as f64 / total,
+        });
+    }
+
+    buckets
+}
+
+/// Fits `S` and `nu` to `data` with simulated annealing, starting from
+/// `S = 400, nu = 1` and cooling geometrically over a fixed iteration
+/// budget. Keeps the best state seen rather than the final one.
+pub fn fit(data: &WDLData) -> DavidsonFit {
+    let buckets = buckets_from(&data.wins, &data.draws, &data.losses);
+
+    if buckets.is_empty() {
+        return DavidsonFit {
+            params: DavidsonParams {
+                scale: 400.0,
+                draw_param: 1.0,
+            },
+            error: 0.0,
+        };
+    }
+
+    let mut rng = rand::thread_rng();
+
+    let mut current = DavidsonParams {
+        scale: 400.0,
+        draw_param: 1.0,
+    };
+    let mut current_error = weighted_squared_error(&buckets, &current);
+
+    let mut best = current;
+    let mut best_error = current_error;
+
+    let iterations = 10_000;
+    let initial_temperature = 1.0;
+    let cooling_rate = 0.999;
+    let mut temperature = initial_temperature;
+
+    for _ in 0..iterations {
+        let mut candidate = current;
+        if rng.gen::<bool>() {
+            candidate.scale += gaussian_step(&mut rng, 10.0);
+            candidate.scale = candidate.scale.max(1.0);
+        } else {
+            candidate.draw_param += gaussian_step(&mut rng, 0.1);
+            candidate.draw_param = candidate.draw_param.max(0.0);
+        }
+
+        let candidate_error = weighted_squared_error(&buckets, &candidate);
+        let delta = candidate_error - current_error;
+
+        if delta < 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+            current = candidate;
+            current_error = candidate_error;
+
+            if current_error < best_error {
+                best = current;
+                best_error = current_error;
+            }
+        }
+
+        temperature *= cooling_rate;
+    }
+
+    DavidsonFit {
+        params: best,
+        error: best_error,
+    }
+}
+
+/// Draws a single sample from `Normal(0, std_dev)` via the Box-Muller
+/// transform, using the two uniform draws `rand` gives us directly.
+fn gaussian_step<R: Rng>(rng: &mut R, std_dev: f64) -> f64 {
+    let u1: f64 = rng.gen::<f64>().max(f64::EPSILON);
+    let u2: f64 = rng.gen::<f64>();
+    let z0 = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+
+    z0 * std_dev
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_predict_equal_ratings_favors_no_one() {
+        let params = DavidsonParams {
+            scale: 400.0,
+            draw_param: 1.0,
+        };
+        let (p_high, _, p_low) = predict(0.0, &params);
+        assert!((p_high - p_low).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_predict_probabilities_sum_to_one() {
+        let params = DavidsonParams {
+            scale: 400.0,
+            draw_param: 0.7,
+        };
+        let (p_high, p_draw, p_low) = predict(150.0, &params);
+        assert!((p_high + p_draw + p_low - 1.0).abs() < 1e-9);
+    }
+}
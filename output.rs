@@ -0,0 +1,256 @@
+//! Renders an aggregated [`WDLData`] as either the original human-readable
+//! console text, or as CSV/JSON/Markdown for spreadsheets, plotting
+//! scripts, or README tables — optionally to a file instead of stdout.
+
+use std::fs::File;
+use std::io::Write;
+
+use super::model;
+use super::{AnalysisConfig, WDLData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Csv,
+    Json,
+    Markdown,
+}
+
+struct Row {
+    rating_diff: i32,
+    win_value: u32,
+    win_total: u32,
+    draw_value: u32,
+    draw_total: u32,
+    loss_value: u32,
+    loss_total: u32,
+}
+
+fn rows(data: &WDLData, config: &AnalysisConfig) -> Vec<Row> {
+    let mut rating_diffs: Vec<i32> = data.wins.keys().cloned().collect();
+    rating_diffs.sort();
+
+    rating_diffs
+        .into_iter()
+        .map(|rating_diff| {
+            let win = data.wins.get(&rating_diff).unwrap();
+            let draw = data.draws.get(&rating_diff).unwrap();
+            let loss = data.losses.get(&rating_diff).unwrap();
+
+            Row {
+                rating_diff,
+                win_value: win.value,
+                win_total: win.total,
+                draw_value: draw.value,
+                draw_total: draw.total,
+                loss_value: loss.value,
+                loss_total: loss.total,
+            }
+        })
+        .filter(|row| row.win_total >= config.min_bucket_samples)
+        .collect()
+}
+
+/// Writes `data` in `format` to `destination`, or to stdout when
+/// `destination` is `None`. Every format except CSV also includes the
+/// model fitted to `data`; CSV holds only the bucket rows. Buckets with
+/// fewer samples than `config`'s threshold are left out of every format.
+pub fn present(data: &WDLData, config: &AnalysisConfig, format: OutputFormat, destination: Option<&str>) {
+    let fit = model::fit(data);
+    let rows = rows(data, config);
+    let rendered = match format {
+        OutputFormat::Text => render_text(&rows, &fit),
+        OutputFormat::Csv => render_csv(&rows),
+        OutputFormat::Json => render_json(&rows, &fit),
+        OutputFormat::Markdown => render_markdown(&rows, &fit),
+    };
+
+    match destination {
+        Some(path) => {
+            let mut file = File::create(path).expect("Failed to create the output file.");
+            file.write_all(rendered.as_bytes())
+                .expect("Failed to write the output file.");
+        }
+        None => print!("{}", rendered),
+    }
+}
+
+fn render_text(rows: &[Row], fit: &model::DavidsonFit) -> String {
+    let mut out = String::new();
+
+    let section = |out: &mut String, title: &str, value_of: &dyn Fn(&Row) -> (u32, u32)| {
+        out.push_str(title);
+        out.push('\n');
+        out.push_str("----------\n");
+        for row in rows {
+            let (value, total) = value_of(row);
+            out.push_str(&format!(
+                "+{}: {} ({}/{})\n",
+                row.rating_diff,
+                percentage(value, total),
+                value,
+                total
+            ));
+        }
+        out.push_str("----------\n");
+    };
+
+    section(&mut out, "Wins", &|row| (row.win_value, row.win_total));
+    section(&mut out, "Draws", &|row| (row.draw_value, row.draw_total));
+    section(&mut out, "Losses", &|row| (row.loss_value, row.loss_total));
+
+    out.push_str("Davidson model fit\n");
+    out.push_str("----------\n");
+    out.push_str(&format!(
+        "S = {:.2}, nu = {:.4} (weighted squared error: {:.6})\n",
+        fit.params.scale, fit.params.draw_param, fit.error
+    ));
+    out.push_str("----------\n");
+
+    out
+}
+
+fn render_csv(rows: &[Row]) -> String {
+    let mut out = String::new();
+    out.push_str("rating_diff,win_value,win_total,win_pct,draw_value,draw_total,draw_pct,loss_value,loss_total,loss_pct\n");
+
+    for row in rows {
+        out.push_str(&format!(
+            "{},{},{},{:.4},{},{},{:.4},{},{},{:.4}\n",
+            row.rating_diff,
+            row.win_value,
+            row.win_total,
+            percentage(row.win_value, row.win_total),
+            row.draw_value,
+            row.draw_total,
+            percentage(row.draw_value, row.draw_total),
+            row.loss_value,
+            row.loss_total,
+            percentage(row.loss_value, row.loss_total),
+        ));
+    }
+
+    out
+}
+
+fn render_json(rows: &[Row], fit: &model::DavidsonFit) -> String {
+    let bucket_entries: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            format!(
+                "{{\"rating_diff\":{},\"wins\":{{\"value\":{},\"total\":{},\"percentage\":{:.4}}},\"draws\":{{\"value\":{},\"total\":{},\"percentage\":{:.4}}},\"losses\":{{\"value\":{},\"total\":{},\"percentage\":{:.4}}}}}",
+                row.rating_diff,
+                row.win_value,
+                row.win_total,
+                percentage(row.win_value, row.win_total),
+                row.draw_value,
+                row.draw_total,
+                percentage(row.draw_value, row.draw_total),
+                row.loss_value,
+                row.loss_total,
+                percentage(row.loss_value, row.loss_total),
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\"buckets\":[{}],\"model\":{{\"scale\":{:.4},\"draw_param\":{:.4},\"error\":{:.6}}}}}\n",
+        bucket_entries.join(","),
+        fit.params.scale,
+        fit.params.draw_param,
+        fit.error
+    )
+}
+
+fn render_markdown(rows: &[Row], fit: &model::DavidsonFit) -> String {
+    let mut out = String::new();
+    out.push_str("| Rating diff | Win % | Win | Draw % | Draw | Loss % | Loss | Total |\n");
+    out.push_str("| --- | --- | --- | --- | --- | --- | --- | --- |\n");
+
+    for row in rows {
+        out.push_str(&format!(
+            "| +{} | {:.2}% | {}/{} | {:.2}% | {}/{} | {:.2}% | {}/{} | {} |\n",
+            row.rating_diff,
+            percentage(row.win_value, row.win_total) * 100.0,
+            row.win_value,
+            row.win_total,
+            percentage(row.draw_value, row.draw_total) * 100.0,
+            row.draw_value,
+            row.draw_total,
+            percentage(row.loss_value, row.loss_total) * 100.0,
+            row.loss_value,
+            row.loss_total,
+            row.win_total,
+        ));
+    }
+
+    out.push_str(&format!(
+        "\nDavidson model fit: `S = {:.2}`, `nu = {:.4}` (weighted squared error: {:.6})\n",
+        fit.params.scale, fit.params.draw_param, fit.error
+    ));
+
+    out
+}
+
+fn percentage(value: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        value as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{AnalysisConfig, Datapoint, Dataset, WDLData};
+
+    fn sample_data() -> WDLData {
+        let mut wins = Dataset::new();
+        wins.insert(0, Datapoint { value: 5, total: 10 });
+        let mut draws = Dataset::new();
+        draws.insert(0, Datapoint { value: 2, total: 10 });
+        let mut losses = Dataset::new();
+        losses.insert(0, Datapoint { value: 3, total: 10 });
+
+        WDLData {
+            wins,
+            draws,
+            losses,
+        }
+    }
+
+    #[test]
+    fn test_render_csv_has_header_and_one_row_per_bucket() {
+        let config = AnalysisConfig::new();
+        let csv = render_csv(&rows(&sample_data(), &config));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("0,5,10"));
+    }
+
+    #[test]
+    fn test_render_markdown_has_header_separator_and_row() {
+        let config = AnalysisConfig::new();
+        let fit = model::DavidsonFit {
+            params: model::DavidsonParams {
+                scale: 400.0,
+                draw_param: 1.0,
+            },
+            error: 0.0,
+        };
+        let markdown = render_markdown(&rows(&sample_data(), &config), &fit);
+        assert!(markdown.starts_with("| Rating diff |"));
+        assert_eq!(
+            markdown.lines().nth(1),
+            Some("| --- | --- | --- | --- | --- | --- | --- | --- |")
+        );
+        assert!(markdown.contains("| +0 |"));
+    }
+
+    #[test]
+    fn test_rows_omits_buckets_below_min_bucket_samples() {
+        let config = AnalysisConfig::new().min_bucket_samples(20);
+        assert!(rows(&sample_data(), &config).is_empty());
+    }
+}
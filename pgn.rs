@@ -0,0 +1,195 @@
+//! A small streaming parser for PGN game exports, built with `nom`.
+//!
+//! Lichess dumps (and PGN exports in general) interleave a block of
+//! bracketed tag pairs with a movetext body, and tolerate blank lines,
+//! comments, NAGs and variations inside that movetext. The previous
+//! line-by-line scanner only understood the common case; this module
+//! parses the actual grammar so game boundaries and tags are recognized
+//! regardless of tag order or of comments, NAGs and variations in the
+//! movetext. A blank line always ends a game's movetext, including one
+//! that falls inside a multi-line comment.
+
+use std::collections::HashMap;
+
+use nom::branch::alt;
+use nom::bytes::complete::{take_till1, take_while, take_while1};
+use nom::character::complete::{char, line_ending};
+use nom::combinator::{map, opt, recognize};
+use nom::error::ErrorKind;
+use nom::multi::{many0, many_till};
+use nom::sequence::{delimited, terminated};
+use nom::IResult;
+
+/// One parsed game: its header tags and the raw movetext that follows them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub headers: HashMap<String, String>,
+    pub movetext: String,
+}
+
+impl Game {
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).map(String::as_str)
+    }
+}
+
+/// A single `[Key "Value"]` tag pair, terminated by its line ending.
+fn header_tag(input: &str) -> IResult<&str, (String, String)> {
+    let (input, _) = char('[')(input)?;
+    let (input, key) = take_while1(|c: char| c.is_alphanumeric())(input)?;
+    let (input, _) = char(' ')(input)?;
+    let (input, value) = delimited(char('"'), take_while(|c| c != '"'), char('"'))(input)?;
+    let (input, _) = char(']')(input)?;
+    let (input, _) = line_ending(input)?;
+
+    Ok((input, (key.to_string(), value.to_string())))
+}
+
+fn header_block(input: &str) -> IResult<&str, HashMap<String, String>> {
+    map(many0(header_tag), |tags| tags.into_iter().collect())(input)
+}
+
+fn blank_line(input: &str) -> IResult<&str, &str> {
+    recognize(terminated(take_while(|c| c == ' ' || c == '\t'), line_ending))(input)
+}
+
+fn blank_lines(input: &str) -> IResult<&str, ()> {
+    map(many0(blank_line), |_| ())(input)
+}
+
+/// Succeeds, consuming nothing, only once `input` is fully consumed.
+fn at_eof(input: &str) -> IResult<&str, &str> {
+    if input.is_empty() {
+        Ok((input, input))
+    } else {
+        Err(nom::Err::Error((input, ErrorKind::Eof)))
+    }
+}
+
+/// A movetext body ends at the blank line separating games, or at EOF for
+/// the last game in a file that has no trailing blank line.
+fn end_of_movetext(input: &str) -> IResult<&str, ()> {
+    alt((map(blank_line, |_| ()), map(at_eof, |_| ())))(input)
+}
+
+fn movetext_line(input: &str) -> IResult<&str, String> {
+    map(
+        terminated(take_till1(|c| c == '\n' || c == '\r'), opt(line_ending)),
+        |l: &str| l.to_string(),
+    )(input)
+}
+
+/// The movetext body: every non-blank line up to the blank line that ends
+/// the game (or EOF), kept verbatim so later passes can inspect moves.
+fn movetext_body(input: &str) -> IResult<&str, String> {
+    let (input, (lines, _)) = many_till(movetext_line, end_of_movetext)(input)?;
+
+    Ok((input, lines.join(" ")))
+}
+
+fn game(input: &str) -> IResult<&str, Game> {
+    let (input, headers) = header_block(input)?;
+    let (input, _) = blank_lines(input)?;
+    let (input, movetext) = movetext_body(input)?;
+    let (input, _) = blank_lines(input)?;
+
+    Ok((input, Game { headers, movetext }))
+}
+
+/// Parses the games out of `contents` lazily, one per call to `next`, in
+/// file order; iteration stops at the first text that doesn't parse as a
+/// game. `contents` is the whole export already held in memory: nothing
+/// is read from a reader. This keeps `calculate` decoupled from the text
+/// format.
+pub fn parse_games<'a>(contents: &'a str) -> impl Iterator<Item = Game> + 'a {
+    let mut remaining = contents;
+
+    std::iter::from_fn(move || {
+        if remaining.trim().is_empty() {
+            return None;
+        }
+        match game(remaining) {
+            Ok((rest, parsed)) if rest != remaining => {
+                remaining = rest;
+                Some(parsed)
+            }
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_tag() {
+        let (rest, (key, value)) = header_tag("[WhiteElo \"1850\"]\n1. e4 e5").unwrap();
+        assert_eq!(key, "WhiteElo");
+        assert_eq!(value, "1850");
+        assert_eq!(rest, "1. e4 e5");
+    }
+
+    #[test]
+    fn test_parse_single_game() {
+        let pgn = "[Event \"Rated Blitz game\"]\n\
+                   [WhiteElo \"1850\"]\n\
+                   [BlackElo \"1800\"]\n\
+                   [Result \"1-0\"]\n\
+                   [TimeControl \"300+3\"]\n\
+                   \n\
+                   1. e4 e5 2. Nf3 Nc6 1-0\n\
+                   \n";
+
+        let games: Vec<Game> = parse_games(pgn).collect();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].header("WhiteElo"), Some("1850"));
+        assert_eq!(games[0].header("Result"), Some("1-0"));
+        assert!(games[0].movetext.contains("e4 e5"));
+    }
+
+    #[test]
+    fn test_parse_multiple_games_regardless_of_header_order() {
+        let pgn = "[Result \"1-0\"]\n\
+                   [WhiteElo \"1850\"]\n\
+                   \n\
+                   1. e4 1-0\n\
+                   \n\
+                   [WhiteElo \"1900\"]\n\
+                   [Result \"0-1\"]\n\
+                   \n\
+                   1. d4 0-1\n\
+                   \n";
+
+        let games: Vec<Game> = parse_games(pgn).collect();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].header("Result"), Some("1-0"));
+        assert_eq!(games[1].header("WhiteElo"), Some("1900"));
+    }
+
+    #[test]
+    fn test_parse_game_with_no_trailing_blank_line() {
+        let pgn = "[WhiteElo \"1850\"]\n\
+                   [Result \"1-0\"]\n\
+                   \n\
+                   1. e4 e5 1-0";
+
+        let games: Vec<Game> = parse_games(pgn).collect();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].movetext, "1. e4 e5 1-0");
+    }
+
+    #[test]
+    fn test_parse_game_with_multi_line_movetext() {
+        let pgn = "[WhiteElo \"1850\"]\n\
+                   [Result \"1-0\"]\n\
+                   \n\
+                   1. e4 e5 2. Nf3 Nc6\n\
+                   3. Bb5 a6 1-0\n\
+                   \n";
+
+        let games: Vec<Game> = parse_games(pgn).collect();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].movetext, "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 1-0");
+    }
+}